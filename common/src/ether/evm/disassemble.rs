@@ -1,11 +1,14 @@
 use std::env;
 use std::fs;
+use std::str::FromStr;
 
-use clap::{AppSettings, Parser};
+use clap::{AppSettings, ArgEnum, Parser};
 use ethers::{
-    core::types::{Address},
-    providers::{Middleware, Provider, Http},
+    core::types::{Address, Block, Bytes, BlockId, BlockNumber, H256},
+    providers::{Middleware, Provider, Http, Ws, Ipc},
+    utils::{keccak256, rlp::{Rlp, RlpStream}},
 };
+use serde::Serialize;
 use heimdall_cache::read_cache;
 use heimdall_cache::store_cache;
 use crate::{
@@ -42,8 +45,378 @@ pub struct DisassemblerArgs {
     #[clap(long, short)]
     pub default: bool,
 
+    /// Verify the fetched bytecode against the state root of the exact block hash passed to
+    /// `--block` before disassembling, the way a light client would, instead of trusting the
+    /// RPC provider. The block hash anchors the trust: the fetched header is re-hashed and
+    /// checked against it before the header's state root is used to verify the account proof,
+    /// so a height or tag like `latest` isn't accepted here.
+    #[clap(long)]
+    pub verify: bool,
+
+    /// The block number, hash, or tag (`latest`/`earliest`) to fetch the bytecode at.
+    #[clap(long="block", short, default_value = "latest")]
+    pub block: String,
+
+    /// The format to output the disassembly in.
+    #[clap(long="format", short, arg_enum, default_value = "asm")]
+    pub format: OutputFormat,
+
+}
+
+/// The output format for a disassembly: a flat assembly listing, or the resolved control-flow
+/// graph as machine-readable JSON or as Graphviz DOT.
+#[derive(Debug, Clone, ArgEnum)]
+pub enum OutputFormat {
+    Asm,
+    Json,
+    Dot,
+}
+
+
+/// Splits a byte slice into its sequence of hex nibbles (4-bit values), most significant first.
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+/// Decodes a hex-prefix encoded trie path (the compact encoding used by all MPT nodes),
+/// returning the nibbles it contains and whether the node it belongs to is a leaf, as opposed
+/// to an extension. Returns `None` for a malformed (empty) path instead of panicking, since
+/// this decodes attacker-controlled proof bytes.
+fn decode_hex_prefix(bytes: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let mut nibbles = to_nibbles(bytes);
+    if nibbles.is_empty() {
+        return None;
+    }
+    let is_leaf = nibbles[0] == 2 || nibbles[0] == 3;
+
+    if nibbles[0] == 1 || nibbles[0] == 3 {
+        nibbles.remove(0);
+    }
+    else {
+        nibbles.drain(0..2);
+    }
+
+    Some((nibbles, is_leaf))
+}
+
+/// Walks an EIP-1186 account proof from a trusted `state_root`, re-deriving the account's
+/// `codeHash` directly from the trie rather than trusting whatever the RPC parsed for us.
+///
+/// At every step, the referenced node's hash (or, for non-root nodes smaller than 32 bytes, its
+/// raw bytes; the root is always referenced by hash) must match the parent's reference, and the branch/extension node must select the
+/// next node using the current nibble of `keccak256(address)`. Returns `None` if the proof is
+/// malformed, the hash chain breaks, or the path never reaches a matching leaf.
+///
+/// Child/extension references are decoded assuming they're always hash references (as they
+/// are for any account trie deep enough to need a 64-nibble path, which is the only case this
+/// function is used for). A node small enough to be RLP-embedded inline rather than hashed
+/// would fail the `as_val::<Bytes>()` decode below and abort verification instead of being
+/// walked; real account-trie proofs don't hit this, so it's left unimplemented rather than
+/// silently mis-verified.
+fn verify_account_proof(state_root: H256, address: Address, account_proof: &[Bytes]) -> Option<H256> {
+    let key_nibbles = to_nibbles(&keccak256(address.as_bytes()));
+    let mut nibble_index = 0;
+    let mut expected_reference = state_root.as_bytes().to_vec();
+
+    for (i, node) in account_proof.iter().enumerate() {
+        let actual_reference = if i == 0 || node.len() >= 32 { keccak256(node).to_vec() } else { node.to_vec() };
+        if actual_reference != expected_reference {
+            return None;
+        }
+
+        let rlp = Rlp::new(node);
+        match rlp.item_count().ok()? {
+
+            // branch node: 16 child references followed by a value slot. a 64-nibble
+            // keccak256 key never terminates exactly on a branch, so just follow the child
+            // selected by the current nibble.
+            17 => {
+                let nibble = *key_nibbles.get(nibble_index)? as usize;
+                let child: Bytes = rlp.at(nibble).ok()?.as_val().ok()?;
+                if child.is_empty() {
+                    return None;
+                }
+
+                expected_reference = child.to_vec();
+                nibble_index += 1;
+            },
+
+            // extension or leaf node: a hex-prefix encoded partial path, plus either the next
+            // node reference (extension) or the RLP-encoded account (leaf).
+            2 => {
+                let path: Bytes = rlp.at(0).ok()?.as_val().ok()?;
+                let (path_nibbles, is_leaf) = decode_hex_prefix(&path)?;
+
+                if key_nibbles.get(nibble_index..nibble_index + path_nibbles.len())? != path_nibbles {
+                    return None;
+                }
+                nibble_index += path_nibbles.len();
+
+                if is_leaf {
+                    // this must be the last node in the proof, exactly consuming the key.
+                    if i != account_proof.len() - 1 || nibble_index != key_nibbles.len() {
+                        return None;
+                    }
+
+                    let value: Bytes = rlp.at(1).ok()?.as_val().ok()?;
+                    let account = Rlp::new(&value);
+                    let code_hash: Bytes = account.at(3).ok()?.as_val().ok()?;
+                    if code_hash.len() != 32 {
+                        return None;
+                    }
+                    return Some(H256::from_slice(&code_hash));
+                }
+                else {
+                    let child: Bytes = rlp.at(1).ok()?.as_val().ok()?;
+                    expected_reference = child.to_vec();
+                }
+            },
+
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+/// Parses a `--block` argument into an ethers `BlockId`, accepting a decimal height, a `0x`
+/// prefixed height or 32-byte block hash, or the `latest`/`earliest` tags.
+fn parse_block_id(block: &str) -> Option<BlockId> {
+    match block {
+        "latest" => Some(BlockId::Number(BlockNumber::Latest)),
+        "earliest" => Some(BlockId::Number(BlockNumber::Earliest)),
+        _ => {
+            if let Some(hex) = block.strip_prefix("0x") {
+                if hex.len() == 64 {
+                    return H256::from_str(hex).ok().map(BlockId::Hash);
+                }
+                return u64::from_str_radix(hex, 16).ok().map(|height| BlockId::Number(BlockNumber::Number(height.into())));
+            }
+
+            block.parse::<u64>().ok().map(|height| BlockId::Number(BlockNumber::Number(height.into())))
+        }
+    }
+}
+
+/// Recomputes an execution block header's hash as `keccak256(rlp(header))`, independently of
+/// whatever `hash` field the RPC response claims, so a malicious provider can't just forge
+/// that field to match a hash we already trust. Supports legacy and EIP-1559 (`baseFeePerGas`)
+/// headers; fields from a later fork (e.g. Shapella's `withdrawalsRoot`) aren't encoded, so
+/// such a header's recomputed hash won't match its real hash and verification fails.
+fn block_header_hash(block: &Block<H256>) -> Option<H256> {
+    let author = block.author?;
+    let logs_bloom = block.logs_bloom?;
+    let number = block.number?;
+    let mix_hash = block.mix_hash?;
+    let nonce = block.nonce?;
+
+    let mut stream = RlpStream::new();
+    stream.begin_list(if block.base_fee_per_gas.is_some() { 16 } else { 15 });
+    stream.append(&block.parent_hash);
+    stream.append(&block.uncles_hash);
+    stream.append(&author);
+    stream.append(&block.state_root);
+    stream.append(&block.transactions_root);
+    stream.append(&block.receipts_root);
+    stream.append(&logs_bloom);
+    stream.append(&block.difficulty);
+    stream.append(&number);
+    stream.append(&block.gas_limit);
+    stream.append(&block.gas_used);
+    stream.append(&block.timestamp);
+    stream.append(&block.extra_data.to_vec());
+    stream.append(&mix_hash);
+    stream.append(&nonce);
+    if let Some(base_fee) = block.base_fee_per_gas {
+        stream.append(&base_fee);
+    }
+
+    Some(H256(keccak256(stream.out())))
+}
+
+/// Fetches the runtime bytecode at `address` and `block` from `provider`, optionally verifying
+/// it against that block's state root the way a light client would, instead of trusting
+/// whatever the RPC provider returns.
+async fn fetch_bytecode<M: Middleware>(provider: &M, address: Address, block: BlockId, verify: bool, logger: &Logger) -> Bytes {
+    let bytecode = match provider.get_code(address, Some(block)).await {
+        Ok(bytecode) => bytecode,
+        Err(_) => {
+            logger.error(&format!("failed to fetch bytecode from '{}' .", address));
+            std::process::exit(1)
+        }
+    };
+
+    if verify {
+        // the RPC is untrusted, so the state root can't come from this same RPC's idea of
+        // "latest"/"block N" - it has to be anchored to a hash the *caller* already trusts,
+        // which we then re-derive from the raw header fields ourselves.
+        let trusted_hash = match block {
+            BlockId::Hash(hash) => hash,
+            BlockId::Number(_) => {
+                logger.error("--verify requires an exact block hash via --block 0x<hash>, so the trusted state root can be anchored independently of the RPC.");
+                std::process::exit(1)
+            }
+        };
+
+        let block_header = match provider.get_block(block).await {
+            Ok(Some(block_header)) => block_header,
+            _ => {
+                logger.error("failed to fetch the block header to verify against.");
+                std::process::exit(1)
+            }
+        };
+
+        let computed_hash = match block_header_hash(&block_header) {
+            Some(hash) => hash,
+            None => {
+                logger.error("failed to verify the block header: missing fields needed to recompute its hash.");
+                std::process::exit(1)
+            }
+        };
+
+        if computed_hash != trusted_hash {
+            logger.error("verification failed: the fetched block header doesn't hash to the trusted block hash.");
+            std::process::exit(1)
+        }
+
+        let proof = match provider.get_proof(address, Vec::new(), block_header.number.map(Into::into)).await {
+            Ok(proof) => proof,
+            Err(_) => {
+                logger.error(&format!("failed to fetch an account proof for '{}' .", address));
+                std::process::exit(1)
+            }
+        };
+
+        let trie_code_hash = match verify_account_proof(block_header.state_root, address, &proof.account_proof) {
+            Some(code_hash) => code_hash,
+            None => {
+                logger.error("account proof verification failed: couldn't walk the proof to a valid leaf.");
+                std::process::exit(1)
+            }
+        };
+
+        if trie_code_hash != H256::from(keccak256(&bytecode)) {
+            logger.error("verification failed: fetched bytecode doesn't match the verified on-chain codeHash.");
+            std::process::exit(1)
+        }
+
+        logger.info("verified fetched bytecode against the trusted block hash's state root.");
+    }
+
+    bytecode
+}
+
+/// A single decoded instruction, keyed by its true program counter (the address of its opcode
+/// byte, not the legacy `.asm` listing's position).
+#[derive(Debug, Clone)]
+struct Instruction {
+    pc: usize,
+    opcode: String,
+    operand: String,
 }
 
+/// A maximal run of instructions with a single entry point and no internal jumps.
+#[derive(Debug, Clone, Serialize)]
+struct BasicBlock {
+    start_pc: usize,
+    end_pc: usize,
+    instructions: Vec<String>,
+    successors: Vec<usize>,
+}
+
+/// Opcodes that end a basic block: unconditional jumps, conditional jumps, and anything that
+/// halts execution.
+const BLOCK_TERMINATORS: [&str; 7] = ["JUMP", "JUMPI", "RETURN", "REVERT", "STOP", "INVALID", "SELFDESTRUCT"];
+
+/// Builds a basic-block control-flow graph from a decoded instruction stream. Every `JUMPDEST`
+/// is marked as a block leader, and blocks are split right after a [`BLOCK_TERMINATORS`]
+/// instruction. Static edges are resolved for the common `PUSHN <dest> JUMP`/`JUMPI` pattern,
+/// where the jump target is a constant pushed by the instruction immediately before it;
+/// dynamic jump targets are left unresolved.
+fn build_cfg(instructions: &[Instruction]) -> Vec<BasicBlock> {
+    let mut leaders: Vec<usize> = vec![0];
+    for (i, instruction) in instructions.iter().enumerate() {
+        if instruction.opcode == "JUMPDEST" {
+            leaders.push(instruction.pc);
+        }
+        if BLOCK_TERMINATORS.contains(&instruction.opcode.as_str()) {
+            if let Some(next) = instructions.get(i + 1) {
+                leaders.push(next.pc);
+            }
+        }
+    }
+    leaders.sort_unstable();
+    leaders.dedup();
+
+    let mut blocks = Vec::new();
+    for (i, &start_pc) in leaders.iter().enumerate() {
+        let end_pc_exclusive = leaders.get(i + 1).copied().unwrap_or(usize::MAX);
+        let block_instructions: Vec<&Instruction> = instructions.iter()
+            .filter(|instruction| instruction.pc >= start_pc && instruction.pc < end_pc_exclusive)
+            .collect();
+
+        let last = match block_instructions.last() {
+            Some(last) => last,
+            None => continue,
+        };
+
+        let mut successors = Vec::new();
+
+        // a static jump target, resolved from the `PUSHN <dest> JUMP`/`JUMPI` pattern, where
+        // the destination is pushed by the instruction immediately before the jump. only kept
+        // if it's actually a block leader (i.e. a JUMPDEST) - a jump into the middle of PUSH
+        // data or some other bogus offset isn't a valid target, and would otherwise produce an
+        // edge to a block that doesn't exist.
+        if (last.opcode == "JUMP" || last.opcode == "JUMPI") && block_instructions.len() >= 2 {
+            let pushed = block_instructions[block_instructions.len() - 2];
+            if pushed.opcode.starts_with("PUSH") && !pushed.operand.is_empty() {
+                if let Ok(dest) = usize::from_str_radix(&pushed.operand, 16) {
+                    if instructions.iter().any(|instruction| instruction.pc == dest && instruction.opcode == "JUMPDEST") {
+                        successors.push(dest);
+                    }
+                }
+            }
+        }
+
+        // JUMPI, and any block that doesn't end in an unconditional terminator, falls through
+        // to the next block in program order.
+        if last.opcode == "JUMPI" || !BLOCK_TERMINATORS.contains(&last.opcode.as_str()) {
+            if let Some(&next_start) = leaders.get(i + 1) {
+                successors.push(next_start);
+            }
+        }
+
+        blocks.push(BasicBlock {
+            start_pc,
+            end_pc: last.pc,
+            instructions: block_instructions.iter().map(|instruction| format!("{} {} {}", instruction.pc, instruction.opcode, instruction.operand)).collect(),
+            successors,
+        });
+    }
+
+    blocks
+}
+
+/// Renders a control-flow graph as Graphviz DOT, for `dot -Tpng`/flamegraph-style
+/// visualization.
+fn cfg_to_dot(blocks: &[BasicBlock]) -> String {
+    let mut dot = String::from("digraph cfg {\n    node [shape=box, fontname=\"monospace\"];\n\n");
+
+    for block in blocks {
+        let label = block.instructions.join("\\l").replace('"', "\\\"");
+        dot.push_str(&format!("    \"{}\" [label=\"{}\\l\"];\n", block.start_pc, label));
+    }
+
+    dot.push('\n');
+    for block in blocks {
+        for successor in &block.successors {
+            dot.push_str(&format!("    \"{}\" -> \"{}\";\n", block.start_pc, successor));
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
 
 pub fn disassemble(args: DisassemblerArgs) -> String {
     use std::time::Instant;
@@ -81,13 +454,28 @@ pub fn disassemble(args: DisassemblerArgs) -> String {
             .build()
             .unwrap();    
 
+        // the block to fetch the bytecode at, folded into the cache key so pinned lookups
+        // don't collide with the (also cached) latest bytecode for the same address.
+        let block = match parse_block_id(&args.block) {
+            Some(block) => block,
+            None => {
+                logger.error(&format!("failed to parse block '{}' .", &args.block));
+                std::process::exit(1)
+            }
+        };
+        let cache_key = format!("contract.{}.{}", &args.target, &args.block);
+
         // We are disassembling a contract address, so we need to fetch the bytecode from the RPC provider.
         contract_bytecode = rt.block_on(async {
 
-            // check the cache for a matching address
-            if let Some(bytecode) = read_cache(&format!("contract.{}", &args.target)) {
-                logger.debug(&format!("found cached bytecode for '{}' .", &args.target));
-                return bytecode;
+            // check the cache for a matching address and block. skipped in --verify mode: a
+            // cache hit would otherwise return bytecode that was never proof-checked against
+            // the state root, silently defeating the point of --verify.
+            if !args.verify {
+                if let Some(bytecode) = read_cache(&cache_key) {
+                    logger.debug(&format!("found cached bytecode for '{}' .", &args.target));
+                    return bytecode;
+                }
             }
 
             // make sure the RPC provider isn't empty
@@ -96,15 +484,6 @@ pub fn disassemble(args: DisassemblerArgs) -> String {
                 std::process::exit(1);
             }
 
-            // create new provider
-            let provider = match Provider::<Http>::try_from(&args.rpc_url) {
-                Ok(provider) => provider,
-                Err(_) => {
-                    logger.error(&format!("failed to connect to RPC provider '{}' .", &args.rpc_url));
-                    std::process::exit(1)
-                }
-            };
-
             // safely unwrap the address
             let address = match args.target.parse::<Address>() {
                 Ok(address) => address,
@@ -114,17 +493,49 @@ pub fn disassemble(args: DisassemblerArgs) -> String {
                 }
             };
 
-            // fetch the bytecode at the address
-            let bytecode_as_bytes = match provider.get_code(address, None).await {
-                Ok(bytecode) => bytecode,
-                Err(_) => {
-                    logger.error(&format!("failed to fetch bytecode from '{}' .", &args.target));
-                    std::process::exit(1)
-                }
+            // fetch the bytecode at the address, using the provider that matches the scheme of
+            // the given RPC url. this allows users to point heimdall at a ws:// or wss://
+            // streaming endpoint, or a local IPC socket, instead of requiring HTTP.
+            let bytecode_as_bytes = if args.rpc_url.starts_with("ws://") || args.rpc_url.starts_with("wss://") {
+                let provider = match Provider::<Ws>::connect(&args.rpc_url).await {
+                    Ok(provider) => provider,
+                    Err(_) => {
+                        logger.error(&format!("failed to connect to RPC provider '{}' .", &args.rpc_url));
+                        std::process::exit(1)
+                    }
+                };
+
+                fetch_bytecode(&provider, address, block, args.verify, &logger).await
+            }
+            else if std::path::Path::new(&args.rpc_url).exists() {
+                // not a ws(s) url, and it points at a real path on disk, so assume it's a
+                // filesystem socket for IPC rather than guessing off the absence of a scheme
+                let provider = match Provider::<Ipc>::connect_ipc(&args.rpc_url).await {
+                    Ok(provider) => provider,
+                    Err(_) => {
+                        logger.error(&format!("failed to connect to RPC provider '{}' .", &args.rpc_url));
+                        std::process::exit(1)
+                    }
+                };
+
+                fetch_bytecode(&provider, address, block, args.verify, &logger).await
+            }
+            else {
+                // default to HTTP. an invalid url surfaces as a connection
+                // error below rather than being silently routed to IPC.
+                let provider = match Provider::<Http>::try_from(&args.rpc_url) {
+                    Ok(provider) => provider,
+                    Err(_) => {
+                        logger.error(&format!("failed to connect to RPC provider '{}' .", &args.rpc_url));
+                        std::process::exit(1)
+                    }
+                };
+
+                fetch_bytecode(&provider, address, block, args.verify, &logger).await
             };
 
             // cache the results
-            store_cache(&format!("contract.{}", &args.target), bytecode_as_bytes.to_string().replacen("0x", "", 1), None);
+            store_cache(&cache_key, bytecode_as_bytes.to_string().replacen("0x", "", 1), None);
 
             bytecode_as_bytes.to_string().replacen("0x", "", 1)
         });
@@ -160,6 +571,7 @@ pub fn disassemble(args: DisassemblerArgs) -> String {
 
     let mut program_counter = 0;
     let mut output: String = String::new();
+    let mut instructions: Vec<Instruction> = Vec::new();
 
     // Iterate over the bytecode, disassembling each instruction.
     let byte_array = contract_bytecode.chars()
@@ -170,12 +582,17 @@ pub fn disassemble(args: DisassemblerArgs) -> String {
 
     while program_counter < byte_array.len(){
 
+        // the opcode's true program counter, before any push data is skipped over. this is
+        // the pc that JUMPDESTs and jump destinations refer to, which the legacy .asm listing
+        // below does not use.
+        let instruction_pc = program_counter;
+
         let operation = opcode(&byte_array[program_counter]);
         let mut pushed_bytes: String = String::new();
 
         if operation.name.contains("PUSH") {
             let byte_count_to_push: u8 = operation.name.replace("PUSH", "").parse().unwrap();
-        
+
             pushed_bytes = match  byte_array.get(program_counter + 1..program_counter + 1 + byte_count_to_push as usize) {
                 Some(bytes) => bytes.join(""),
                 None => {
@@ -184,7 +601,8 @@ pub fn disassemble(args: DisassemblerArgs) -> String {
             };
             program_counter += byte_count_to_push as usize;
         }
-        
+
+        instructions.push(Instruction { pc: instruction_pc, opcode: operation.name.clone(), operand: pushed_bytes.clone() });
 
         output.push_str(format!("{} {} {}\n", program_counter, operation.name, pushed_bytes).as_str());
         program_counter += 1;
@@ -192,13 +610,122 @@ pub fn disassemble(args: DisassemblerArgs) -> String {
 
     logger.info(&format!("disassembled {program_counter} bytes successfully."));
 
+    // select the output representation: the flat .asm listing, or the resolved control-flow
+    // graph rendered as JSON or DOT.
+    let (output, format_extension) = match args.format {
+        OutputFormat::Asm => (output, "asm"),
+        OutputFormat::Json => {
+            let cfg = build_cfg(&instructions);
+            (serde_json::to_string_pretty(&cfg).unwrap_or_default(), "json")
+        },
+        OutputFormat::Dot => (cfg_to_dot(&build_cfg(&instructions)), "dot"),
+    };
+
     // write the output to a file
     write_file(&format!("{output_dir}/bytecode.evm"), &contract_bytecode);
-    let file_path = write_file(&format!("{output_dir}/disassembled.asm"), &output);
+    let file_path = write_file(&format!("{output_dir}/disassembled.{format_extension}"), &output);
     logger.success(&format!("wrote disassembled bytecode to '{file_path}' ."));
 
     // log the time it took to disassemble the bytecode
     logger.debug(&format!("disassembly completed in {} ms.", now.elapsed().as_millis()));
-    
+
     output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::core::types::U256;
+
+    /// Hex-prefix encodes a path, the inverse of `decode_hex_prefix`, so tests can build valid
+    /// trie nodes without hand-computing the encoding.
+    fn encode_hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+        let odd = nibbles.len() % 2 == 1;
+        let flag = match (is_leaf, odd) {
+            (false, false) => 0u8,
+            (false, true) => 1,
+            (true, false) => 2,
+            (true, true) => 3,
+        };
+
+        let mut all_nibbles = vec![flag];
+        if !odd {
+            all_nibbles.push(0);
+        }
+        all_nibbles.extend_from_slice(nibbles);
+
+        all_nibbles.chunks(2).map(|pair| (pair[0] << 4) | pair[1]).collect()
+    }
+
+    /// RLP-encodes the `[nonce, balance, storageHash, codeHash]` account tuple a leaf's value
+    /// decodes to.
+    fn encode_account(nonce: u64, balance: u64, storage_hash: H256, code_hash: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new();
+        stream.begin_list(4);
+        stream.append(&U256::from(nonce));
+        stream.append(&U256::from(balance));
+        stream.append(&storage_hash);
+        stream.append(&code_hash.to_vec());
+        stream.out().to_vec()
+    }
+
+    #[test]
+    fn decode_hex_prefix_rejects_an_empty_path() {
+        assert_eq!(decode_hex_prefix(&[]), None);
+    }
+
+    #[test]
+    fn verify_account_proof_walks_a_single_leaf_root() {
+        let address = Address::from_low_u64_be(0xdead_beef);
+        let key_nibbles = to_nibbles(&keccak256(address.as_bytes()));
+        let code_hash = keccak256(b"some bytecode").to_vec();
+        let account_rlp = encode_account(1, 2, H256::zero(), &code_hash);
+
+        let mut leaf = RlpStream::new();
+        leaf.begin_list(2);
+        leaf.append(&encode_hex_prefix(&key_nibbles, true));
+        leaf.append(&account_rlp);
+        let leaf_bytes = leaf.out().to_vec();
+
+        let state_root = H256(keccak256(&leaf_bytes));
+        let proof = vec![Bytes::from(leaf_bytes)];
+
+        assert_eq!(verify_account_proof(state_root, address, &proof), Some(H256::from_slice(&code_hash)));
+    }
+
+    #[test]
+    fn verify_account_proof_rejects_a_short_code_hash_without_panicking() {
+        let address = Address::from_low_u64_be(0xdead_beef);
+        let key_nibbles = to_nibbles(&keccak256(address.as_bytes()));
+        let short_code_hash = vec![0u8; 31];
+        let account_rlp = encode_account(1, 2, H256::zero(), &short_code_hash);
+
+        let mut leaf = RlpStream::new();
+        leaf.begin_list(2);
+        leaf.append(&encode_hex_prefix(&key_nibbles, true));
+        leaf.append(&account_rlp);
+        let leaf_bytes = leaf.out().to_vec();
+
+        let state_root = H256(keccak256(&leaf_bytes));
+        let proof = vec![Bytes::from(leaf_bytes)];
+
+        assert_eq!(verify_account_proof(state_root, address, &proof), None);
+    }
+
+    #[test]
+    fn verify_account_proof_rejects_an_empty_branch_child() {
+        let address = Address::from_low_u64_be(0xdead_beef);
+
+        let mut branch = RlpStream::new();
+        branch.begin_list(17);
+        for _ in 0..17 {
+            branch.append_empty_data();
+        }
+        let branch_bytes = branch.out().to_vec();
+
+        let state_root = H256(keccak256(&branch_bytes));
+        let proof = vec![Bytes::from(branch_bytes)];
+
+        assert_eq!(verify_account_proof(state_root, address, &proof), None);
+    }
 }
\ No newline at end of file